@@ -1,8 +1,10 @@
 use std::borrow::Cow;
 use std::fmt;
 use std::num::{ParseFloatError, ParseIntError};
+use std::ops::Range;
 use std::path::PathBuf;
 use std::string::FromUtf8Error;
+use wain_ast::Module;
 use wain_syntax_text::lexer::{LexError, Token};
 use wain_syntax_text::parser::ParseError;
 use wain_syntax_text::source::describe_position;
@@ -45,20 +47,102 @@ pub enum ParseKind<'source> {
     Wat2Wasm(TransformError<'source>),
 }
 
+impl<'source> ParseKind<'source> {
+    // Whether this failure is merely syntactic and a driver may reasonably keep going (e.g. an
+    // interactive REPL requesting more input on `EndOfFile`), as opposed to a fatal failure that
+    // invalidates the whole input (`Utf8Error`, a failed WAT-to-WASM transform).
+    pub fn recoverable(&self) -> bool {
+        use ParseKind::*;
+        !matches!(self, Utf8Error(_) | Wat2Wasm(_))
+    }
+
+    // The byte range the error covers, derived from the offending token whenever the variant carries
+    // it: `InvalidStringLiteral` keeps its literal slice and `Unexpected` keeps the token it rejected
+    // (whose rendered form matches its source width for keywords, identifiers, numbers and parens).
+    // The remaining variants carry no source extent, and the `From`-converted lexer/parser errors
+    // expose a single offset, so those collapse to an empty `pos..pos` range and render as a point.
+    fn error_span(&self, pos: usize) -> Range<usize> {
+        let width = match self {
+            ParseKind::InvalidStringLiteral { lit, .. } => lit.len(),
+            ParseKind::Unexpected {
+                token: Some(token), ..
+            } => token.to_string().len(),
+            _ => 0,
+        };
+        pos..pos + width
+    }
+}
+
+// Stable identifier for each parse failure, suitable for programmatic consumption (editor/LSP, CI).
+// New variants may be added over time, so downstream `match`es must keep a wildcard arm.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    UnexpectedToken,
+    UnexpectedEof,
+    Utf8,
+    InvalidStringLiteral,
+    InvalidInt,
+    TooSmallInt,
+    InvalidFloat,
+    InvalidHexFloat,
+    Lex,
+    Wat,
+    Wat2Wasm,
+}
+
+impl ErrorCode {
+    // The dotted, stable string form of the code, e.g. `wain::parse::invalid_int`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::UnexpectedToken => "wain::parse::unexpected_token",
+            ErrorCode::UnexpectedEof => "wain::parse::unexpected_eof",
+            ErrorCode::Utf8 => "wain::parse::utf8",
+            ErrorCode::InvalidStringLiteral => "wain::parse::invalid_string_literal",
+            ErrorCode::InvalidInt => "wain::parse::invalid_int",
+            ErrorCode::TooSmallInt => "wain::parse::too_small_int",
+            ErrorCode::InvalidFloat => "wain::parse::invalid_float",
+            ErrorCode::InvalidHexFloat => "wain::parse::invalid_hex_float",
+            ErrorCode::Lex => "wain::parse::lex",
+            ErrorCode::Wat => "wain::parse::wat",
+            ErrorCode::Wat2Wasm => "wain::parse::wat2wasm",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 pub struct Error<'source> {
     pub pos: usize,
+    pub span: Range<usize>,
     source: &'source str,
     kind: ErrorKind<'source>,
+    context: Vec<&'static str>,
     pub prev_error: Option<Box<Error<'source>>>,
     file: Option<PathBuf>,
 }
 
 impl<'s> Error<'s> {
     pub fn parse_error(kind: ParseKind<'s>, source: &'s str, pos: usize) -> Box<Error<'s>> {
+        let span = kind.error_span(pos);
+        Error::parse_error_span(kind, source, span)
+    }
+
+    pub fn parse_error_span(
+        kind: ParseKind<'s>,
+        source: &'s str,
+        span: Range<usize>,
+    ) -> Box<Error<'s>> {
         Box::new(Error {
-            pos,
+            pos: span.start,
+            span,
             source,
             kind: ErrorKind::Parse(kind),
+            context: Vec::new(),
             prev_error: None,
             file: None,
         })
@@ -67,12 +151,84 @@ impl<'s> Error<'s> {
     pub fn set_file(&mut self, p: PathBuf) {
         self.file = Some(p);
     }
+
+    // The typed failure, so callers can branch on it instead of string-matching `Display` output.
+    pub fn kind(&self) -> &ErrorKind<'s> {
+        &self.kind
+    }
+
+    // Whether this error is recoverable (syntactic) rather than fatal. See
+    // [`ParseKind::recoverable`].
+    pub fn recoverable(&self) -> bool {
+        match &self.kind {
+            ErrorKind::Parse(kind) => kind.recoverable(),
+        }
+    }
+}
+
+// Annotate a failing sub-parse with the construct that was being built, so that `Display` can print
+// the layered trail ("while parsing function signature", "while parsing module") instead of a
+// single hard-coded label. Frames are pushed as the error unwinds, so the innermost construct is
+// reported first.
+pub trait ErrorContext {
+    fn context(self, ctx: &'static str) -> Self;
+}
+
+impl<'s, T> ErrorContext for Result<'s, T> {
+    fn context(self, ctx: &'static str) -> Self {
+        self.map_err(|mut err| {
+            err.context.push(ctx);
+            err
+        })
+    }
+}
+
+// Resolve a byte offset into a 1-based line number and a 0-based column (byte distance from the
+// start of its line).
+fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(source.len());
+    let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line_no = source[..offset].bytes().filter(|&b| b == b'\n').count() + 1;
+    (line_no, offset - line_start)
+}
+
+// Render the source line containing `span` followed by a caret/underline marker aligned to the
+// offending columns, like `rustc` and `pest` do. The line number is one more than the count of '\n'
+// bytes before `span.start`; the caret is indented by the byte distance from the start of that line,
+// and the printed `col:` is that distance plus one. Spans crossing a line boundary are underlined only
+// through the end of their first line.
+fn describe_span(f: &mut fmt::Formatter<'_>, source: &str, span: &Range<usize>) -> fmt::Result {
+    let start = span.start.min(source.len());
+    let end = span.end.min(source.len()).max(start);
+
+    let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[start..]
+        .find('\n')
+        .map_or(source.len(), |i| start + i);
+    let line = source[line_start..line_end].trim_end_matches('\r');
+
+    let (line_no, col) = line_col(source, start);
+    let width = (end.min(line_end) - start).max(1);
+
+    let number = line_no.to_string();
+    let gutter = " ".repeat(number.len());
+    write!(f, "\n{} | {}", number, line)?;
+    write!(
+        f,
+        "\n{} | {}{}",
+        gutter,
+        " ".repeat(col),
+        "^".repeat(width)
+    )?;
+    write!(f, "\n{} at line:{} col:{}", gutter, line_no, col + 1)
 }
 
 macro_rules! parse_error_from {
     ($from:ty, $kind:ident) => {
         impl<'s> From<Box<$from>> for Box<Error<'s>> {
             fn from(err: Box<$from>) -> Box<Error<'s>> {
+                // These wrapped errors expose only a single `offset()`, so the resulting span stays
+                // empty and rendering falls back to the single-position pointer.
                 let source = err.source();
                 let offset = err.offset();
                 Error::parse_error(ParseKind::$kind(*err), source, offset)
@@ -84,55 +240,239 @@ parse_error_from!(LexError<'s>, Lex);
 parse_error_from!(ParseError<'s>, ParseWat);
 parse_error_from!(TransformError<'s>, Wat2Wasm);
 
-impl<'s> fmt::Display for Error<'s> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let doing = match &self.kind {
+// Buffer that accumulates diagnostics during a recovering parse instead of bailing out at the first
+// failure. Driven by [`parse_recovering`], which parses each top-level definition on its own and
+// records the failures, so a single pass over a malformed file reports every broken definition at
+// once instead of only the first.
+#[derive(Default)]
+pub struct Diagnostics<'source> {
+    errors: Vec<Box<Error<'source>>>,
+}
+
+impl<'s> Diagnostics<'s> {
+    pub fn new() -> Self {
+        Diagnostics { errors: Vec::new() }
+    }
+
+    pub fn push(&mut self, error: Box<Error<'s>>) {
+        self.errors.push(error);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    // Drop cascading diagnostics whose span is fully contained within an earlier one's span, the way
+    // rustc suppresses follow-on move errors. Errors are kept in reporting order and an earlier error
+    // always wins over a later one nested inside it.
+    pub fn suppress_contained(mut self) -> Vec<Box<Error<'s>>> {
+        let mut kept: Vec<Box<Error<'s>>> = Vec::with_capacity(self.errors.len());
+        for error in self.errors.drain(..) {
+            let contained = kept.iter().any(|prev| {
+                prev.span.start <= error.span.start && error.span.end <= prev.span.end
+            });
+            if !contained {
+                kept.push(error);
+            }
+        }
+        kept
+    }
+
+    pub fn into_vec(self) -> Vec<Box<Error<'s>>> {
+        self.errors
+    }
+}
+
+// Split `source` into its balanced top-level (paren depth zero) s-expressions, returning the byte
+// range of each. Parens inside line comments (`;; …`), nestable block comments (`(; … ;)`) and
+// string literals are ignored so they don't throw off the nesting, and an unterminated final form
+// is still returned so its error gets reported.
+fn top_level_forms(source: &str) -> Vec<Range<usize>> {
+    let b = source.as_bytes();
+    let n = b.len();
+    let mut forms = Vec::new();
+    let mut depth = 0usize;
+    let mut start = None;
+    let mut i = 0;
+    while i < n {
+        // line comment: skip to the end of the line
+        if b[i] == b';' && i + 1 < n && b[i + 1] == b';' {
+            i += 2;
+            while i < n && b[i] != b'\n' {
+                i += 1;
+            }
+            continue;
+        }
+        // block comment: skip to the matching `;)`, honouring nesting
+        if b[i] == b'(' && i + 1 < n && b[i + 1] == b';' {
+            let mut comment = 1usize;
+            i += 2;
+            while i < n && comment > 0 {
+                if b[i] == b'(' && i + 1 < n && b[i + 1] == b';' {
+                    comment += 1;
+                    i += 2;
+                } else if b[i] == b';' && i + 1 < n && b[i + 1] == b')' {
+                    comment -= 1;
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            continue;
+        }
+        // string literal: skip to the closing quote, respecting escapes
+        if b[i] == b'"' {
+            i += 1;
+            while i < n {
+                match b[i] {
+                    b'\\' => i += 2,
+                    b'"' => {
+                        i += 1;
+                        break;
+                    }
+                    _ => i += 1,
+                }
+            }
+            continue;
+        }
+        match b[i] {
+            b'(' => {
+                if depth == 0 {
+                    start = Some(i);
+                }
+                depth += 1;
+            }
+            b')' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    if let Some(s) = start.take() {
+                        forms.push(s..i + 1);
+                    }
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    if let Some(s) = start {
+        forms.push(s..n);
+    }
+    forms
+}
+
+// Whether the top-level form at `range` opens with the `module` keyword, i.e. it is a module
+// definition this parser can take on (as opposed to a `.wast` script command such as `assert_*`).
+fn is_module_form(source: &str, range: &Range<usize>) -> bool {
+    source[range.start + 1..range.end]
+        .trim_start()
+        .starts_with("module")
+}
+
+// Parse `source` in one shot, mapping the underlying parser failure onto our `Error` type via the
+// existing `From` conversions.
+fn parse_once(source: &str) -> Result<'_, Module<'_>> {
+    Ok(wain_syntax_text::parse(source)?)
+}
+
+// Recovering entry point: instead of bailing at the first failure, parse every top-level module
+// definition independently so a single pass reports each malformed one. A module that parses cleanly
+// yields no diagnostic, so valid definitions never produce spurious errors. When the whole input is
+// one well-formed module it is returned directly; otherwise the collected diagnostics are returned,
+// deduplicated so cascading errors nested inside an earlier one are suppressed.
+pub fn parse_recovering(source: &str) -> (Option<Module<'_>>, Vec<Box<Error<'_>>>) {
+    if let Ok(module) = parse_once(source) {
+        return (Some(module), Vec::new());
+    }
+
+    let mut diags = Diagnostics::new();
+    for range in top_level_forms(source) {
+        if !is_module_form(source, &range) {
+            continue;
+        }
+        if let Err(mut err) = parse_once(&source[range.clone()]) {
+            // Shift the form-local offsets back onto the full source so snippets and carets still
+            // line up with the original text.
+            let base = range.start;
+            err.pos += base;
+            err.span.start += base;
+            err.span.end += base;
+            err.source = source;
+            diags.push(err);
+        }
+    }
+
+    (None, diags.suppress_contained())
+}
+
+impl<'s> Error<'s> {
+    // Write just the failure message for this error's kind, without the trailing context trail,
+    // file name or source snippet. Shared by `Display` and the machine-readable `message` field.
+    fn fmt_kind(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
             ErrorKind::Parse(kind) => {
                 use ParseKind::*;
                 match kind {
-                    Lex(err) => write!(f, "lexer error: {}", err)?,
-                    ParseWat(err) => write!(f, "parse error on parsing WAT module: {}", err)?,
-                    Wat2Wasm(err) => write!(f, "could not transform from WAT to WASM: {}", err)?,
+                    Lex(err) => write!(f, "lexer error: {}", err),
+                    ParseWat(err) => write!(f, "parse error on parsing WAT module: {}", err),
+                    Wat2Wasm(err) => write!(f, "could not transform from WAT to WASM: {}", err),
                     Unexpected {
                         expected,
                         token: None,
-                    } => write!(f, "unexpected token while {} is expected", expected)?,
+                    } => write!(f, "unexpected token while {} is expected", expected),
                     Unexpected {
                         expected,
                         token: Some(token),
-                    } => write!(
-                        f,
-                        "unexpected token {} while {} is expected",
-                        token, expected
-                    )?,
+                    } => write!(f, "unexpected token {} while {} is expected", token, expected),
                     EndOfFile { expected } => {
-                        write!(f, "unxpected EOF while {} is expected", expected)?
+                        write!(f, "unxpected EOF while {} is expected", expected)
                     }
-                    Utf8Error(err) => write!(f, "cannot parse text as UTF-8: {}", err)?,
+                    Utf8Error(err) => write!(f, "cannot parse text as UTF-8: {}", err),
                     InvalidStringLiteral { lit, reason } => {
-                        write!(f, "invalid string literal '{}': {}", lit, reason)?
+                        write!(f, "invalid string literal '{}': {}", lit, reason)
                     }
-                    InvalidInt { ty, err } => write!(f, "invalid int literal for {}: {}", ty, err)?,
+                    InvalidInt { ty, err } => write!(f, "invalid int literal for {}: {}", ty, err),
                     TooSmallInt { ty, digits } => {
-                        write!(f, "-{} is too small value for {}", digits, ty)?
+                        write!(f, "-{} is too small value for {}", digits, ty)
                     }
                     InvalidFloat { ty, err } => {
-                        write!(f, "invalid float number literal for {}: {}", ty, err)?
+                        write!(f, "invalid float number literal for {}: {}", ty, err)
                     }
                     InvalidHexFloat { ty } => {
-                        write!(f, "invalid hex float number literal for {}", ty)?
+                        write!(f, "invalid hex float number literal for {}", ty)
                     }
                 }
-                "parsing"
             }
-        };
+        }
+    }
+}
+
+impl<'s> fmt::Display for Error<'s> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_kind(f)?;
 
-        write!(f, " while {}", doing)?;
+        if self.context.is_empty() {
+            write!(f, " while parsing")?;
+        } else {
+            for (i, ctx) in self.context.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ",")?;
+                }
+                write!(f, " while parsing {}", ctx)?;
+            }
+        }
         if let Some(path) = &self.file {
             write!(f, " '{:?}'", path)?;
         }
 
-        describe_position(f, self.source, self.pos)?;
+        if self.span.end > self.span.start {
+            describe_span(f, self.source, &self.span)?;
+        } else {
+            describe_position(f, self.source, self.pos)?;
+        }
 
         if let Some(prev) = &self.prev_error {
             write!(
@@ -152,4 +492,289 @@ impl<'s> fmt::Debug for Error<'s> {
     }
 }
 
+// Stable diagnostic code for each parse failure. Kept separate from `Display` so tools can key on
+// it without scraping the rendered message.
+impl<'s> Error<'s> {
+    pub fn code(&self) -> ErrorCode {
+        use ParseKind::*;
+        match &self.kind {
+            ErrorKind::Parse(kind) => match kind {
+                Unexpected { .. } => ErrorCode::UnexpectedToken,
+                EndOfFile { .. } => ErrorCode::UnexpectedEof,
+                Utf8Error(_) => ErrorCode::Utf8,
+                InvalidStringLiteral { .. } => ErrorCode::InvalidStringLiteral,
+                InvalidInt { .. } => ErrorCode::InvalidInt,
+                TooSmallInt { .. } => ErrorCode::TooSmallInt,
+                InvalidFloat { .. } => ErrorCode::InvalidFloat,
+                InvalidHexFloat { .. } => ErrorCode::InvalidHexFloat,
+                Lex(_) => ErrorCode::Lex,
+                ParseWat(_) => ErrorCode::Wat,
+                Wat2Wasm(_) => ErrorCode::Wat2Wasm,
+            },
+        }
+    }
+}
+
+// Write the per-diagnostic fields (`code`, `message`, `file`, byte `offset`, `line`, `column`,
+// `span`) shared by the top-level error and its `related` entries. The `related` array itself is
+// added only at the top level so each diagnostic is emitted exactly once.
+#[cfg(feature = "serde")]
+fn serialize_fields<S>(err: &Error<'_>, s: &mut S) -> ::std::result::Result<(), S::Error>
+where
+    S: serde::ser::SerializeStruct,
+{
+    let (line, column) = line_col(err.source, err.pos);
+    s.serialize_field("code", err.code().as_str())?;
+    s.serialize_field("message", &SerializeDisplay(&|f| err.fmt_kind(f)))?;
+    s.serialize_field("file", &err.file.as_ref().map(|p| p.display().to_string()))?;
+    s.serialize_field("offset", &err.pos)?;
+    s.serialize_field("line", &line)?;
+    s.serialize_field("column", &(column + 1))?;
+    s.serialize_field("span", &[err.span.start, err.span.end])?;
+    Ok(())
+}
+
+// A single diagnostic serialized *without* its own `prev_error` chain, used for the entries of a
+// top-level error's `related` array so a deep chain doesn't emit the same error more than once.
+#[cfg(feature = "serde")]
+struct FlatError<'a, 's>(&'a Error<'s>);
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for FlatError<'_, '_> {
+    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+        let mut s = serializer.serialize_struct("Error", 7)?;
+        serialize_fields(self.0, &mut s)?;
+        s.end()
+    }
+}
+
+// Serialize a diagnostic as a flat JSON object: `code`, `message`, `file`, byte `offset`, `line`,
+// `column`, `span` and a `related` array walked from the `prev_error` chain. Enabled by the `serde`
+// feature so programs (editors, LSP servers, CI) can consume wain's output reliably.
+#[cfg(feature = "serde")]
+impl<'s> serde::Serialize for Error<'s> {
+    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+
+        // Flatten the `prev_error` chain into `related`; each entry is serialized without its own
+        // chain (see `FlatError`) so a diagnostic never appears twice.
+        let mut related: Vec<FlatError<'_, 's>> = Vec::new();
+        let mut prev = self.prev_error.as_deref();
+        while let Some(err) = prev {
+            related.push(FlatError(err));
+            prev = err.prev_error.as_deref();
+        }
+
+        let mut s = serializer.serialize_struct("Error", 8)?;
+        serialize_fields(self, &mut s)?;
+        s.serialize_field("related", &related)?;
+        s.end()
+    }
+}
+
+// Serialize an arbitrary `fmt::Display`-style closure as a JSON string, letting the serializer
+// stream the formatted output via `collect_str`.
+#[cfg(feature = "serde")]
+struct SerializeDisplay<'a>(&'a dyn Fn(&mut fmt::Formatter<'_>) -> fmt::Result);
+
+#[cfg(feature = "serde")]
+impl<'a> fmt::Display for SerializeDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (self.0)(f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'a> serde::Serialize for SerializeDisplay<'a> {
+    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'s> std::error::Error for Error<'s> {
+    // Implementing `std::error::Error` lets wain errors compose with the wider Rust error ecosystem
+    // (`?`, `anyhow`, `Box<dyn Error>`). The only errors we can surface through `source()` are the
+    // UTF-8, int and float failures: `std::error::Error::source` is bound to `dyn Error + 'static`,
+    // and the wrapped `LexError`/`ParseError`/`TransformError` all borrow the `'source` text, so they
+    // cannot be coerced to a `'static` trait object. Callers that need those should match on
+    // [`Error::kind`].
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ErrorKind::Parse(ParseKind::Utf8Error(err)) => Some(err),
+            ErrorKind::Parse(ParseKind::InvalidInt { err, .. }) => Some(err),
+            ErrorKind::Parse(ParseKind::InvalidFloat { err, .. }) => Some(err),
+            ErrorKind::Parse(_) => None,
+        }
+    }
+}
+
+// Serve the error's own source text to miette, labelling it with the `file` field (as a
+// `NamedSource` would) so the file name appears in the rendered report. The borrowed `'source` text
+// can't satisfy `NamedSource`'s `'static` bound, so we attach the name to the returned span contents
+// instead.
+#[cfg(feature = "miette")]
+impl<'s> miette::SourceCode for Error<'s> {
+    fn read_span<'a>(
+        &'a self,
+        span: &miette::SourceSpan,
+        context_lines_before: usize,
+        context_lines_after: usize,
+    ) -> ::std::result::Result<Box<dyn miette::SpanContents<'a> + 'a>, miette::MietteError> {
+        let contents = self
+            .source
+            .read_span(span, context_lines_before, context_lines_after)?;
+        match &self.file {
+            Some(path) => Ok(Box::new(miette::MietteSpanContents::new_named(
+                path.display().to_string(),
+                contents.data(),
+                *contents.span(),
+                contents.line(),
+                contents.column(),
+                contents.line_count(),
+            ))),
+            None => Ok(contents),
+        }
+    }
+}
+
+#[cfg(feature = "miette")]
+impl<'s> miette::Diagnostic for Error<'s> {
+    fn code(&self) -> Option<Box<dyn fmt::Display + '_>> {
+        Some(Box::new(self.code()))
+    }
+
+    fn help(&self) -> Option<Box<dyn fmt::Display + '_>> {
+        use ParseKind::*;
+        match &self.kind {
+            ErrorKind::Parse(TooSmallInt { ty, .. }) => {
+                Some(Box::new(format!("the value is below the minimum of {}", ty)))
+            }
+            ErrorKind::Parse(InvalidHexFloat { .. }) => Some(Box::new(
+                "hex floats look like `0x1.fp3`: a `0x` prefix, hex mantissa and an optional `p` exponent",
+            )),
+            _ => None,
+        }
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        Some(self)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        let len = self.span.end.saturating_sub(self.span.start);
+        let label = miette::LabeledSpan::new(Some("here".to_string()), self.span.start, len);
+        Some(Box::new(std::iter::once(label)))
+    }
+}
+
 pub type Result<'s, T> = ::std::result::Result<T, Box<Error<'s>>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Render a span through `describe_span` so its output can be asserted as a string.
+    struct Snippet<'a>(&'a str, Range<usize>);
+
+    impl fmt::Display for Snippet<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            describe_span(f, self.0, &self.1)
+        }
+    }
+
+    #[test]
+    fn line_col_resolves_line_and_column() {
+        let src = "abc\ndefg\nhi";
+        assert_eq!(line_col(src, 0), (1, 0));
+        assert_eq!(line_col(src, 2), (1, 2));
+        assert_eq!(line_col(src, 4), (2, 0));
+        assert_eq!(line_col(src, 6), (2, 2));
+        // offsets past the end clamp to the final position
+        assert_eq!(line_col(src, 999), (3, 2));
+    }
+
+    #[test]
+    fn describe_span_underlines_the_offending_columns() {
+        let src = "(module\n  (func $f))";
+        let start = src.find("func").unwrap();
+        let rendered = Snippet(src, start..start + 4).to_string();
+        assert_eq!(rendered, "\n2 |   (func $f))\n  |    ^^^^\n  at line:2 col:4");
+    }
+
+    #[test]
+    fn describe_span_clamps_multiline_and_empty_spans() {
+        let src = "abc\ndef";
+        // a span crossing the newline underlines only through the first line's end
+        assert!(Snippet(src, 1..6).to_string().contains("\n1 | abc\n  |  ^^"));
+        // an empty span still renders a single caret, even at an out-of-range offset
+        assert!(Snippet(src, 99..99).to_string().contains('^'));
+    }
+
+    #[test]
+    fn error_span_covers_the_offending_literal() {
+        let kind = ParseKind::InvalidStringLiteral {
+            lit: "hello",
+            reason: "bad escape",
+        };
+        assert_eq!(kind.error_span(3), 3..8);
+        // variants without a stored extent stay a single point
+        assert_eq!(ParseKind::EndOfFile { expected: "x" }.error_span(3), 3..3);
+    }
+
+    #[test]
+    fn top_level_forms_splits_on_depth_zero_parens() {
+        let src = "(module (func))\n(module)";
+        assert_eq!(top_level_forms(src), vec![0..15, 16..24]);
+    }
+
+    #[test]
+    fn top_level_forms_ignores_parens_in_strings_and_comments() {
+        let src = "(a \")(\") ;; )\n(b)";
+        assert_eq!(top_level_forms(src), vec![0..8, 14..17]);
+    }
+
+    #[test]
+    fn is_module_form_detects_the_leading_keyword() {
+        let src = "(module)(assert_return)";
+        assert!(is_module_form(src, &(0..8)));
+        assert!(!is_module_form(src, &(8..23)));
+    }
+
+    #[test]
+    fn suppress_contained_drops_nested_errors() {
+        let src = "0123456789";
+        let mut diags = Diagnostics::new();
+        diags.push(Error::parse_error_span(
+            ParseKind::EndOfFile { expected: "outer" },
+            src,
+            0..6,
+        ));
+        // fully inside the first error's span -> suppressed
+        diags.push(Error::parse_error_span(
+            ParseKind::EndOfFile { expected: "inner" },
+            src,
+            2..4,
+        ));
+        // disjoint -> kept
+        diags.push(Error::parse_error_span(
+            ParseKind::EndOfFile { expected: "later" },
+            src,
+            7..9,
+        ));
+
+        let kept = diags.suppress_contained();
+        assert_eq!(kept.len(), 2);
+        assert_eq!(kept[0].span, 0..6);
+        assert_eq!(kept[1].span, 7..9);
+    }
+}